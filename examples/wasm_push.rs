@@ -9,6 +9,7 @@ async fn main() -> Result<()> {
         "https://ghcr.io/xxx/xxx".to_string(),
         "username".to_string(),
         "some_token".to_string(),
+        None,
     )
     .await?;
     Ok(())
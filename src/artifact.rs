@@ -0,0 +1,285 @@
+//!  SPDX-License-Identifier: MIT
+//!
+//! Copyright (c) 2023, eunomia-bpf
+//! All rights reserved.
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use log::info;
+use oci_distribution::{
+    client::{Config, ImageLayer},
+    manifest,
+};
+use url::Url;
+
+use super::{auth, sign, wasm::parse_img_url};
+
+/// The `org.opencontainers.image.title` annotation key used to recover a layer's
+/// original file name on pull
+const TITLE_ANNOTATION: &str = "org.opencontainers.image.title";
+/// Media type for a whole directory packed into a single layer
+pub const TAR_LAYER_MEDIA_TYPE: &str = "application/x-tar";
+
+const DEFAULT_CONFIG_MEDIA_TYPE: &str = "application/vnd.oci.image.config.v1+json";
+
+/// One file (or directory) to package into an artifact image
+pub struct ArtifactEntry {
+    /// Path to the file or directory to package
+    pub path: PathBuf,
+    /// OCI media type for this entry. A directory must use
+    /// [`TAR_LAYER_MEDIA_TYPE`]; it is archived into a single layer.
+    pub media_type: String,
+}
+
+/// Configuration for pushing a multi-file eBPF/OCI artifact
+pub struct ArtifactPushArgs {
+    /// Registry URL to push to
+    pub image_url: String,
+    /// OCI username
+    pub username: String,
+    /// OCI password
+    pub password: String,
+    /// Files (or directories) to package as layers, in push order
+    pub entries: Vec<ArtifactEntry>,
+    /// Media type for the image config blob; defaults to
+    /// `application/vnd.oci.image.config.v1+json`
+    pub config_media_type: Option<String>,
+    /// Raw JSON config blob; defaults to `{}`
+    pub config: Option<Vec<u8>>,
+    /// Annotations to attach to the manifest
+    pub annotations: Option<HashMap<String, String>>,
+}
+
+fn read_entry(path: &Path, media_type: &str) -> Result<Vec<u8>> {
+    if path.is_dir() {
+        if media_type != TAR_LAYER_MEDIA_TYPE {
+            return Err(anyhow!(
+                "{} is a directory and must use media type {}",
+                path.display(),
+                TAR_LAYER_MEDIA_TYPE
+            ));
+        }
+        let mut builder = tar::Builder::new(Vec::new());
+        builder.append_dir_all(".", path)?;
+        builder.into_inner().map_err(|e| anyhow!(e))
+    } else {
+        fs::read(path).map_err(|e| anyhow!("failed to read {}: {}", path.display(), e))
+    }
+}
+
+/// Push a multi-file eBPF/OCI artifact: an ordered set of files (or whole
+/// directories, packed as a single [`TAR_LAYER_MEDIA_TYPE`] layer) is pushed as one
+/// image, the way artifact-packaging tools bundle an object file, its BTF/skeleton,
+/// and metadata into a single distributable unit. Returns the manifest url.
+///
+/// `username`/`password` may be left empty, in which case credentials are
+/// resolved via [`auth::resolve_auth`].
+pub async fn push_artifact(args: ArtifactPushArgs) -> Result<String> {
+    if args.entries.is_empty() {
+        return Err(anyhow!("no entries to push"));
+    }
+
+    let (mut client, reference, repo_url) = parse_img_url(&args.image_url)?;
+    info!("pushing artifact to {}", repo_url);
+
+    let image_url = Url::parse(&args.image_url)?;
+    let auth = auth::resolve_auth(
+        &image_url,
+        &args.username,
+        &args.password,
+        &auth::default_auth_file_path(),
+    )
+    .await;
+
+    let mut layers = Vec::with_capacity(args.entries.len());
+    for entry in &args.entries {
+        let data = read_entry(&entry.path, &entry.media_type)?;
+        let mut annotations = HashMap::new();
+        if let Some(name) = entry.path.file_name() {
+            annotations.insert(TITLE_ANNOTATION.to_string(), name.to_string_lossy().into());
+        }
+        layers.push(ImageLayer::new(
+            data,
+            entry.media_type.clone(),
+            Some(annotations),
+        ));
+    }
+
+    let config = Config {
+        data: args.config.unwrap_or_else(|| b"{}".to_vec()),
+        media_type: args
+            .config_media_type
+            .unwrap_or_else(|| DEFAULT_CONFIG_MEDIA_TYPE.to_string()),
+        annotations: None,
+    };
+
+    let image_manifest = manifest::OciImageManifest::build(&layers, &config, args.annotations);
+
+    let resp = client
+        .push(&reference, &layers, config, &auth, Some(image_manifest))
+        .await?;
+
+    Ok(resp.manifest_url)
+}
+
+/// Configuration for pulling a multi-file eBPF/OCI artifact
+pub struct ArtifactPullArgs {
+    /// Registry URL to pull from
+    pub image_url: String,
+    /// OCI username
+    pub username: String,
+    /// OCI password
+    pub password: String,
+    /// Directory to write the pulled layers into
+    pub output_dir: String,
+    /// Layer media types to select from the manifest
+    pub media_types: Vec<String>,
+}
+
+/// Pull a multi-file eBPF/OCI artifact, selecting only the manifest layers whose
+/// media type is in `media_types` and writing each one back out under
+/// `output_dir`. A [`TAR_LAYER_MEDIA_TYPE`] layer is unpacked as a directory
+/// instead of written out as a single file. Unlike `Client::pull`, layers outside
+/// `media_types` are simply skipped rather than failing the whole pull. Returns
+/// the paths written.
+///
+/// `username`/`password` may be left empty, in which case credentials are
+/// resolved via [`auth::resolve_auth`].
+pub async fn pull_artifact(args: ArtifactPullArgs) -> Result<Vec<PathBuf>> {
+    if args.media_types.is_empty() {
+        return Err(anyhow!("no media types to pull"));
+    }
+
+    let (mut client, reference, repo_url) = parse_img_url(&args.image_url)?;
+    info!("pulling artifact from {}", repo_url);
+
+    let image_url = Url::parse(&args.image_url)?;
+    let auth = auth::resolve_auth(
+        &image_url,
+        &args.username,
+        &args.password,
+        &auth::default_auth_file_path(),
+    )
+    .await;
+    let (manifest, _) = client.pull_image_manifest(&reference, &auth).await?;
+
+    let output_dir = PathBuf::from(&args.output_dir);
+    fs::create_dir_all(&output_dir)?;
+
+    let mut written = Vec::with_capacity(manifest.layers.len());
+    for (idx, descriptor) in manifest.layers.iter().enumerate() {
+        if !args.media_types.iter().any(|m| m == &descriptor.media_type) {
+            continue;
+        }
+
+        let mut data = Vec::new();
+        client
+            .pull_blob(&reference, &descriptor.digest, &mut data)
+            .await?;
+        sign::verify_layer_digest(&data, &descriptor.digest)?;
+
+        if descriptor.media_type == TAR_LAYER_MEDIA_TYPE {
+            tar::Archive::new(&data[..]).unpack(&output_dir)?;
+            written.push(output_dir.clone());
+            continue;
+        }
+
+        let file_name = descriptor
+            .annotations
+            .as_ref()
+            .and_then(|a| a.get(TITLE_ANNOTATION))
+            .and_then(|name| sanitize_file_name(name))
+            .unwrap_or_else(|| format!("layer-{idx}"));
+        let out_path = output_dir.join(file_name);
+        fs::write(&out_path, &data)?;
+        written.push(out_path);
+    }
+
+    if written.is_empty() {
+        return Err(anyhow!(
+            "no layers in {} matched the requested media types",
+            repo_url
+        ));
+    }
+
+    Ok(written)
+}
+
+/// Reduce a registry-supplied `org.opencontainers.image.title` annotation to a bare
+/// file name, keeping only its last path component (leading directories are
+/// dropped) and rejecting a bare `..`, so a malicious registry can't write
+/// outside `output_dir` via the annotation.
+fn sanitize_file_name(name: &str) -> Option<String> {
+    Path::new(name)
+        .file_name()
+        .map(|f| f.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_entry, sanitize_file_name, TAR_LAYER_MEDIA_TYPE};
+    use std::fs;
+
+    #[test]
+    fn test_sanitize_file_name_rejects_traversal() {
+        assert_eq!(
+            sanitize_file_name("../../etc/passwd").as_deref(),
+            Some("passwd")
+        );
+        assert_eq!(
+            sanitize_file_name("/etc/passwd").as_deref(),
+            Some("passwd")
+        );
+        assert_eq!(sanitize_file_name("report.json").as_deref(), Some("report.json"));
+        assert_eq!(sanitize_file_name(".."), None);
+    }
+
+    #[test]
+    fn test_read_entry_file() {
+        let dir = tempdir();
+        let file_path = dir.join("obj.o");
+        fs::write(&file_path, b"some eBPF object file bytes").unwrap();
+
+        let data = read_entry(&file_path, "application/octet-stream").unwrap();
+        assert_eq!(data, b"some eBPF object file bytes");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_entry_dir_packs_and_unpacks_as_tar() {
+        let dir = tempdir();
+        let src = dir.join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("a.txt"), b"hello").unwrap();
+
+        let tar_bytes = read_entry(&src, TAR_LAYER_MEDIA_TYPE).unwrap();
+
+        let out = dir.join("out");
+        fs::create_dir_all(&out).unwrap();
+        tar::Archive::new(&tar_bytes[..]).unpack(&out).unwrap();
+        assert_eq!(fs::read(out.join("a.txt")).unwrap(), b"hello");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_entry_dir_requires_tar_media_type() {
+        let dir = tempdir();
+        fs::create_dir_all(&dir).unwrap();
+        assert!(read_entry(&dir, "application/octet-stream").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn tempdir() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "simoci_artifact_test_{:?}",
+            std::thread::current().id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+}
@@ -2,7 +2,9 @@
 //!
 //! Copyright (c) 2023, eunomia-bpf
 //! All rights reserved.
+pub mod artifact;
 pub mod auth;
+pub mod sign;
 mod wasm;
 
 use anyhow::{anyhow, Result};
@@ -46,7 +48,19 @@ pub fn get_client(url: &Url) -> Result<Client> {
 
 /// Push an image to the OCI registry
 pub async fn push(args: PushArgs) -> Result<()> {
-    wasm_push(args.file, args.image_url, args.username, args.password).await?;
+    let sign_key = args
+        .sign_key
+        .as_deref()
+        .map(sign::load_signing_key)
+        .transpose()?;
+    wasm_push(
+        args.file,
+        args.image_url,
+        args.username,
+        args.password,
+        sign_key,
+    )
+    .await?;
     Ok(())
 }
 
@@ -61,7 +75,19 @@ pub async fn pull(args: PullArgs) -> Result<()> {
     } else {
         File::create(&args.write_file).await?
     };
-    let data = wasm_pull(args.image_url.as_str(), args.username, args.password).await?;
+    let verify_key = args
+        .verify_key
+        .as_deref()
+        .map(sign::load_verifying_key)
+        .transpose()?;
+    let data = wasm_pull(
+        args.image_url.as_str(),
+        args.username,
+        args.password,
+        args.anonymous,
+        verify_key,
+    )
+    .await?;
     io::copy(&mut &data[..], &mut file).await?;
     Ok(())
 }
@@ -0,0 +1,180 @@
+//!  SPDX-License-Identifier: MIT
+//!
+//! Copyright (c) 2023, eunomia-bpf
+//! All rights reserved.
+//!
+//! Detached `v4.public`-style PASETO signing and digest verification for pushed artifacts.
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose, Engine};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey, SIGNATURE_LENGTH};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+
+/// The manifest annotation key a push signature is stored under
+pub const SIGNATURE_ANNOTATION: &str = "dev.eunomia.signature";
+
+/// `v4.public.`, the PASETO header that starts the signed message inside each token
+const HEADER: &[u8] = b"v4.public.";
+
+/// The claims signed over a pushed artifact
+#[derive(Serialize, Deserialize)]
+struct Claims {
+    digest: String,
+    iat: u64,
+    sub: String,
+}
+
+/// The SHA-256 digest of `data`, formatted as `sha256:<hex>`
+pub fn sha256_digest(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    format!("sha256:{:x}", hasher.finalize())
+}
+
+/// Recompute the SHA-256 of `data` and compare it against `expected_digest`
+/// (`sha256:<hex>`), guarding against a misbehaving or MITM'd registry serving
+/// altered bytes that still happen to parse as valid.
+pub fn verify_layer_digest(data: &[u8], expected_digest: &str) -> Result<()> {
+    let actual_digest = sha256_digest(data);
+    if actual_digest != expected_digest {
+        return Err(anyhow!(
+            "digest mismatch: expected {} got {}",
+            expected_digest,
+            actual_digest
+        ));
+    }
+    Ok(())
+}
+
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Load an ed25519 signing (private) key from `path`: either 32 raw bytes, or a
+/// PEM-encoded PKCS#8 private key
+pub fn load_signing_key(path: &Path) -> Result<SigningKey> {
+    let data = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let seed = key_bytes_from_file(&data, "private")?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// Load an ed25519 verifying (public) key from `path`: either 32 raw bytes, or a
+/// PEM-encoded public key
+pub fn load_verifying_key(path: &Path) -> Result<VerifyingKey> {
+    let data = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let raw = key_bytes_from_file(&data, "public")?;
+    VerifyingKey::from_bytes(&raw).context("invalid ed25519 public key")
+}
+
+/// Pull the 32 raw key bytes out of a key file, accepting either a raw 32-byte key
+/// or a PEM-encoded one (the DER payload of an ed25519 PEM key always ends in the
+/// 32-byte raw key).
+fn key_bytes_from_file(data: &[u8], kind: &str) -> Result<[u8; 32]> {
+    if data.len() == 32 {
+        let mut key = [0u8; 32];
+        key.copy_from_slice(data);
+        return Ok(key);
+    }
+
+    let text = std::str::from_utf8(data)
+        .with_context(|| format!("{} key file is neither 32 raw bytes nor valid PEM text", kind))?;
+    let parsed = pem::parse(text).with_context(|| format!("failed to parse PEM {} key", kind))?;
+    let der = parsed.contents();
+    if der.len() < 32 {
+        return Err(anyhow!("PEM {} key is too short to contain an ed25519 key", kind));
+    }
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&der[der.len() - 32..]);
+    Ok(key)
+}
+
+/// Sign `digest` (the SHA-256 digest of the layer being pushed, `sha256:<hex>`) for
+/// `image_ref`, producing a `v4.public`-style PASETO token:
+/// `base64url(header || payload || ed25519_sign(header || payload))`.
+pub fn sign_digest(signing_key: &SigningKey, digest: &str, image_ref: &str) -> Result<String> {
+    let claims = Claims {
+        digest: digest.to_string(),
+        iat: now_unix(),
+        sub: image_ref.to_string(),
+    };
+    let payload = serde_json::to_vec(&claims)?;
+
+    let mut message = Vec::with_capacity(HEADER.len() + payload.len());
+    message.extend_from_slice(HEADER);
+    message.extend_from_slice(&payload);
+
+    let signature = signing_key.sign(&message);
+
+    let mut token = message;
+    token.extend_from_slice(&signature.to_bytes());
+    Ok(general_purpose::URL_SAFE_NO_PAD.encode(token))
+}
+
+/// Verify a `v4.public`-style token produced by [`sign_digest`] against `verify_key`,
+/// and confirm its signed `digest` claim equals `expected_digest`.
+pub fn verify_digest(verify_key: &VerifyingKey, token: &str, expected_digest: &str) -> Result<()> {
+    let decoded = general_purpose::URL_SAFE_NO_PAD
+        .decode(token)
+        .context("signature token is not valid base64url")?;
+    if decoded.len() < SIGNATURE_LENGTH {
+        return Err(anyhow!("signature token is too short"));
+    }
+    let (message, sig_bytes) = decoded.split_at(decoded.len() - SIGNATURE_LENGTH);
+
+    let signature = Signature::from_slice(sig_bytes).context("malformed ed25519 signature")?;
+    verify_key
+        .verify(message, &signature)
+        .map_err(|_| anyhow!("signature verification failed"))?;
+
+    let payload = message
+        .strip_prefix(HEADER)
+        .ok_or_else(|| anyhow!("unexpected token header"))?;
+    let claims: Claims =
+        serde_json::from_slice(payload).context("failed to parse signed claims")?;
+
+    if claims.digest != expected_digest {
+        return Err(anyhow!(
+            "signed digest {} does not match artifact digest {}",
+            claims.digest,
+            expected_digest
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{sha256_digest, sign_digest, verify_digest, verify_layer_digest};
+    use ed25519_dalek::SigningKey;
+
+    #[test]
+    fn test_verify_layer_digest() {
+        let data = b"hello world".to_vec();
+        let digest = sha256_digest(&data);
+
+        assert!(verify_layer_digest(&data, &digest).is_ok());
+        assert!(verify_layer_digest(&data, "sha256:deadbeef").is_err());
+    }
+
+    #[test]
+    fn test_sign_and_verify_round_trip() {
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let verifying_key = signing_key.verifying_key();
+
+        let digest = sha256_digest(b"some eBPF object file bytes");
+        let token = sign_digest(&signing_key, &digest, "ghcr.io/xxx/xxx:latest").unwrap();
+
+        assert!(verify_digest(&verifying_key, &token, &digest).is_ok());
+        assert!(verify_digest(&verifying_key, &token, "sha256:deadbeef").is_err());
+
+        let other_key = SigningKey::from_bytes(&[9u8; 32]).verifying_key();
+        assert!(verify_digest(&other_key, &token, &digest).is_err());
+    }
+}
@@ -6,6 +6,7 @@ use std::path::Path;
 
 use anyhow::anyhow;
 use anyhow::Result;
+use ed25519_dalek::{SigningKey, VerifyingKey};
 use log::info;
 use std::collections::HashMap;
 use tokio::{fs::File, io::AsyncReadExt};
@@ -19,7 +20,7 @@ use oci_distribution::{
     Client, Reference,
 };
 
-use super::{auth, default_schema_port, get_client};
+use super::{auth, default_schema_port, get_client, sign};
 
 /// Parse the URL, return things that will be used for pushing / pulling
 /// returns (..., repo_url_strip_auth_info)
@@ -38,11 +39,16 @@ pub fn parse_img_url(url: &str) -> anyhow::Result<(Client, Reference, String)> {
 }
 
 /// Push an image
+///
+/// `username`/`password` may be left empty, in which case credentials are
+/// resolved via [`auth::resolve_auth`] (URL userinfo, then the login cache,
+/// then Docker's config, then anonymous).
 pub async fn wasm_push(
     file: String,
     img_url: String,
     username: String,
     password: String,
+    sign_key: Option<SigningKey>,
 ) -> Result<()> {
     let path = Path::new(&file);
 
@@ -57,18 +63,58 @@ pub async fn wasm_push(
     wasmparser::validate(&module)?;
 
     let (mut client, reference, _) = parse_img_url(&img_url)?;
-    let auth = auth::get_registry_auth(username, password);
-    push_wasm_to_registry(&mut client, &auth, &reference, module, None).await?;
+    let parsed_url = Url::parse(&img_url)?;
+    let auth = auth::resolve_auth(
+        &parsed_url,
+        &username,
+        &password,
+        &auth::default_auth_file_path(),
+    )
+    .await;
+    push_wasm_to_registry(
+        &mut client,
+        &auth,
+        &reference,
+        module,
+        None,
+        sign_key.as_ref(),
+    )
+    .await?;
     Ok(())
 }
 
 /// Pull an image
-pub async fn wasm_pull(img: &str, username: String, password: String) -> Result<Vec<u8>> {
+///
+/// `username`/`password` may be left empty, in which case credentials are
+/// resolved via [`auth::resolve_auth`] (URL userinfo, then the login cache,
+/// then Docker's config, then an anonymous pull). Set `anonymous` to skip credential resolution
+/// entirely and pull as an anonymous client. When `verify_key` is set, the
+/// artifact must carry a valid signature over its digest or the pull fails;
+/// leave it `None` to skip signature verification entirely.
+pub async fn wasm_pull(
+    img: &str,
+    username: String,
+    password: String,
+    anonymous: bool,
+    verify_key: Option<VerifyingKey>,
+) -> Result<Vec<u8>> {
     let (mut client, reference, repo_url) = parse_img_url(img)?;
     info!("pulling from {}", repo_url);
 
-    let auth = auth::get_registry_auth(username, password);
-    let img_content = pull_wasm_from_registry(&mut client, &auth, &reference).await?;
+    let auth = if anonymous {
+        RegistryAuth::Anonymous
+    } else {
+        let img_url = Url::parse(img)?;
+        auth::resolve_auth(
+            &img_url,
+            &username,
+            &password,
+            &auth::default_auth_file_path(),
+        )
+        .await
+    };
+    let img_content =
+        pull_wasm_from_registry(&mut client, &auth, &reference, verify_key.as_ref()).await?;
     info!(
         "successful pull {} bytes from {}",
         img_content.len(),
@@ -88,21 +134,77 @@ pub struct PullArgs {
     pub username: String,
     /// oci password
     pub password: String,
+    /// Skip credential resolution and pull anonymously, for public images
+    pub anonymous: bool,
+    /// Path to an ed25519 public key (PEM or raw 32-byte) the artifact's signature
+    /// must verify against; leave unset to skip signature verification
+    pub verify_key: Option<std::path::PathBuf>,
 }
 
 pub(super) async fn pull_wasm_from_registry(
     client: &mut Client,
     auth: &RegistryAuth,
     reference: &Reference,
+    verify_key: Option<&VerifyingKey>,
 ) -> Result<Vec<u8>> {
-    if let Some(img_data) = client
+    let image_data = match client
         .pull(reference, auth, vec![manifest::WASM_LAYER_MEDIA_TYPE])
-        .await?
-        .layers
-        .into_iter()
-        .next()
-        .map(|layer| layer.data)
+        .await
     {
+        Ok(data) => data,
+        // A registry may reject credentialed requests for a public image (or reject
+        // stale cached credentials); fall back to the anonymous, public-read path
+        // before giving up.
+        Err(e) if !matches!(auth, RegistryAuth::Anonymous) && is_unauthorized(&e) => {
+            info!("authenticated pull was unauthorized, retrying anonymously");
+            client
+                .pull(
+                    reference,
+                    &RegistryAuth::Anonymous,
+                    vec![manifest::WASM_LAYER_MEDIA_TYPE],
+                )
+                .await?
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let manifest = image_data
+        .manifest
+        .as_ref()
+        .ok_or_else(|| anyhow!("registry did not return a manifest"))?;
+    let descriptor = manifest
+        .layers
+        .first()
+        .ok_or_else(|| anyhow!("manifest has no layers"))?;
+    let expected_digest = descriptor.digest.clone();
+
+    // If the caller asked for a specific digest (`...@sha256:...`), that addresses
+    // the manifest itself, not a layer — compare it against the manifest's own
+    // content digest, not the layer digest checked below.
+    if let Some(requested_digest) = reference.digest() {
+        let manifest_digest = image_data
+            .digest
+            .as_deref()
+            .ok_or_else(|| anyhow!("registry did not return a manifest digest"))?;
+        if requested_digest != manifest_digest {
+            return Err(anyhow!(
+                "digest mismatch: requested {} but received manifest {}",
+                requested_digest,
+                manifest_digest
+            ));
+        }
+    }
+
+    if let Some(img_data) = image_data.layers.into_iter().next().map(|layer| layer.data) {
+        sign::verify_layer_digest(&img_data, &expected_digest)?;
+        if let Some(key) = verify_key {
+            let token = manifest
+                .annotations
+                .as_ref()
+                .and_then(|a| a.get(sign::SIGNATURE_ANNOTATION))
+                .ok_or_else(|| anyhow!("artifact is not signed but a verify key was supplied"))?;
+            sign::verify_digest(key, token, &expected_digest)?;
+        }
         Ok(img_data)
     } else {
         let repo_url = format!(
@@ -115,6 +217,18 @@ pub(super) async fn pull_wasm_from_registry(
     }
 }
 
+fn is_unauthorized(err: &oci_distribution::errors::OciDistributionError) -> bool {
+    use oci_distribution::errors::OciDistributionError;
+    match err {
+        OciDistributionError::UnauthorizedError { .. } => true,
+        OciDistributionError::RequestError(e) => {
+            e.status() == Some(reqwest::StatusCode::UNAUTHORIZED)
+        }
+        OciDistributionError::ServerError { code, .. } => *code == 401,
+        _ => false,
+    }
+}
+
 /// Configuration for a pushing process
 pub struct PushArgs {
     /// Local file path
@@ -125,6 +239,9 @@ pub struct PushArgs {
     pub username: String,
     /// password
     pub password: String,
+    /// Path to an ed25519 private key (PEM or raw 32-byte) to sign the pushed
+    /// artifact's digest with; leave unset to push unsigned
+    pub sign_key: Option<std::path::PathBuf>,
 }
 // return the manifest url
 pub async fn push_wasm_to_registry(
@@ -133,7 +250,21 @@ pub async fn push_wasm_to_registry(
     reference: &Reference,
     module: Vec<u8>,
     annotations: Option<HashMap<String, String>>,
+    sign_key: Option<&SigningKey>,
 ) -> Result<String> {
+    let mut annotations = annotations.unwrap_or_default();
+    if let Some(key) = sign_key {
+        let digest = sign::sha256_digest(&module);
+        let image_ref = format!("{}/{}", reference.registry(), reference.repository());
+        let token = sign::sign_digest(key, &digest, &image_ref)?;
+        annotations.insert(sign::SIGNATURE_ANNOTATION.to_string(), token);
+    }
+    let annotations = if annotations.is_empty() {
+        None
+    } else {
+        Some(annotations)
+    };
+
     let layers = vec![ImageLayer::new(
         module,
         manifest::WASM_LAYER_MEDIA_TYPE.to_string(),